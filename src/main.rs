@@ -1,4 +1,10 @@
-use chrono::{DateTime, Duration, Local, NaiveTime, TimeZone};
+use chrono::{
+    DateTime, Datelike, Duration, FixedOffset, Local, LocalResult, NaiveDate, NaiveDateTime,
+    NaiveTime, TimeZone,
+};
+use chrono_humanize::HumanTime;
+use chrono_tz::Tz;
+use git2::{Repository, Sort};
 use rand::Rng;
 use serde::{Deserialize, Serialize};
 use std::process::Command;
@@ -6,21 +12,165 @@ use std::process::Command;
 type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
 
 // --- 配置结构体 ---
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 struct AppConfig {
-    start_time: String,
-    end_time: String,
+    /// 旧版单一时间区间配置，仅在 `schedule` 缺失时用于迁移，新配置请
+    /// 直接编辑 `schedule`
+    #[serde(default)]
+    start_time: Option<String>,
+    #[serde(default)]
+    end_time: Option<String>,
+    /// 每周的时间窗口安排；首次从旧版配置加载时会自动迁移生成
+    #[serde(default)]
+    schedule: Option<WeeklySchedule>,
+    /// IANA 时区名称（如 `Asia/Shanghai`），为空则使用系统本地时区
+    #[serde(default)]
+    timezone: Option<String>,
+    /// 随机化哪一个时间戳：作者时间、提交者时间，还是两者都随机
+    #[serde(default)]
+    date_mode: DateMode,
+    /// 当 `date_mode` 为 `Both` 时，是否让提交者时间在作者时间之后再
+    /// 偏移一小段随机时间，使提交看起来因果上仍然自洽
+    #[serde(default)]
+    stagger_committer: bool,
+    /// 展示随机时间时使用的 `strftime` 格式
+    #[serde(default = "default_display_format")]
+    display_format: String,
+}
+
+fn default_display_format() -> String {
+    "%Y-%m-%d %H:%M:%S %z".to_string()
 }
 
 impl Default for AppConfig {
     fn default() -> Self {
         Self {
-            start_time: "00:00".to_string(),
-            end_time: "02:00".to_string(),
+            start_time: None,
+            end_time: None,
+            schedule: Some(WeeklySchedule::uniform(TimeWindow {
+                start_time: "00:00".to_string(),
+                end_time: "02:00".to_string(),
+            })),
+            timezone: None,
+            date_mode: DateMode::default(),
+            stagger_committer: false,
+            display_format: default_display_format(),
         }
     }
 }
 
+/// 一个时间窗口，如午休窗口或深夜窗口
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct TimeWindow {
+    start_time: String,
+    end_time: String,
+}
+
+/// 某一天的安排：是否启用，以及当天可选的若干时间窗口
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct DaySchedule {
+    #[serde(default = "default_enabled")]
+    enabled: bool,
+    windows: Vec<TimeWindow>,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// 一周七天各自的安排，支持跳过某些天（如周末）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct WeeklySchedule {
+    mon: DaySchedule,
+    tue: DaySchedule,
+    wed: DaySchedule,
+    thu: DaySchedule,
+    fri: DaySchedule,
+    sat: DaySchedule,
+    sun: DaySchedule,
+}
+
+impl WeeklySchedule {
+    /// 用同一个时间窗口填满周一到周日，等价于迁移前的单一区间配置
+    fn uniform(window: TimeWindow) -> Self {
+        let day = || DaySchedule {
+            enabled: true,
+            windows: vec![window.clone()],
+        };
+        Self {
+            mon: day(),
+            tue: day(),
+            wed: day(),
+            thu: day(),
+            fri: day(),
+            sat: day(),
+            sun: day(),
+        }
+    }
+
+    fn day(&self, weekday: chrono::Weekday) -> &DaySchedule {
+        use chrono::Weekday::*;
+        match weekday {
+            Mon => &self.mon,
+            Tue => &self.tue,
+            Wed => &self.wed,
+            Thu => &self.thu,
+            Fri => &self.fri,
+            Sat => &self.sat,
+            Sun => &self.sun,
+        }
+    }
+
+    /// 按周一到周日的顺序列出 (中文名称, 当天安排)，供 `show` 命令展示
+    fn days(&self) -> [(&'static str, &DaySchedule); 7] {
+        [
+            ("周一", &self.mon),
+            ("周二", &self.tue),
+            ("周三", &self.wed),
+            ("周四", &self.thu),
+            ("周五", &self.fri),
+            ("周六", &self.sat),
+            ("周日", &self.sun),
+        ]
+    }
+}
+
+/// 控制 `GIT_AUTHOR_DATE` / `GIT_COMMITTER_DATE` 中哪些被随机化
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+enum DateMode {
+    /// 作者时间和提交者时间都随机化（默认）
+    Both,
+    /// 只随机化作者时间，提交者时间使用 git 的默认行为（当前时间）
+    AuthorOnly,
+    /// 只随机化提交者时间，作者时间使用 git 的默认行为（当前时间）
+    CommitterOnly,
+}
+
+impl Default for DateMode {
+    fn default() -> Self {
+        DateMode::Both
+    }
+}
+
+/// 持久化的 `--spread` 会话游标，使连续多次 `run_commit` 调用能够分摊到
+/// 同一天窗口内的不同时间槽，而不是每次都独立随机
+///
+/// 注意：这个游标通过 confy 存在用户级别的配置文件里，并不区分仓库，
+/// 所以同一天在多个仓库里分别使用 `--spread` 会共享同一个游标（即互相
+/// 抢占槽位）。目前按单仓库使用场景设计，如果需要跨仓库隔离，需要改为
+/// 以仓库路径为 key 存储
+#[derive(Debug, Serialize, Deserialize, Default)]
+struct SpreadSession {
+    /// 本轮分摊所针对的日期（`%Y-%m-%d`）
+    date: Option<String>,
+    /// 本轮分摊的总槽数
+    #[serde(default)]
+    count: usize,
+    /// 已经用掉的槽位数
+    #[serde(default)]
+    cursor: usize,
+}
+
 // --- 主函数：命令行入口 ---
 fn main() -> Result<()> {
     let cli: Vec<String> = std::env::args().skip(1).collect();
@@ -29,9 +179,11 @@ fn main() -> Result<()> {
         println!(
             r#"Usage:
 git-tc set <start> <end>
+git-tc set-format <strftime 格式>
 git-tc show
-git-tc amend
-git-tc ...
+git-tc amend [--tz <IANA 时区>] [--author-only|--committer-only|--both] [--format <strftime>]
+git-tc reschedule [--from <rev>] [--apply] [--tz <IANA 时区>]
+git-tc ... [--tz <IANA 时区>] [--author-only|--committer-only|--both] [--spread <count>] [--format <strftime>]
 "#
         );
         return Ok(());
@@ -45,14 +197,31 @@ git-tc ...
             }
             set_time_range(&cli[1], &cli[2])?;
         }
+        "set-format" => {
+            if cli.len() != 2 {
+                println!("用法: `git-tc set-format <strftime 格式>`");
+                return Ok(());
+            }
+            set_display_format(&cli[1])?;
+        }
         "show" => {
             show_time_range()?;
         }
         "amend" => {
-            amend_commit_time(&cli[1..])?;
+            let (tz, rest) = extract_tz_override(&cli[1..])?;
+            let (mode, rest) = extract_date_mode_override(&rest)?;
+            let (format, rest) = extract_format_override(&rest)?;
+            amend_commit_time(&rest, tz, mode, format)?;
+        }
+        "reschedule" => {
+            reschedule_history(&cli[1..])?;
         }
         _ => {
-            run_commit(&cli)?;
+            let (tz, rest) = extract_tz_override(&cli)?;
+            let (mode, rest) = extract_date_mode_override(&rest)?;
+            let (spread, rest) = extract_spread_override(&rest)?;
+            let (format, rest) = extract_format_override(&rest)?;
+            run_commit(&rest, tz, mode, spread, format)?;
         }
     }
 
@@ -61,6 +230,24 @@ git-tc ...
 
 // --- 核心功能函数 ---
 
+/// 加载配置；如果发现的是迁移前只有 `start_time`/`end_time` 的旧版配置，
+/// 自动将其迁移为 Mon-Sun 统一的 `schedule` 并写回磁盘
+fn load_config() -> Result<AppConfig> {
+    let mut cfg: AppConfig = confy::load("git-touchfish-commit", None)?;
+
+    if cfg.schedule.is_none() {
+        let window = TimeWindow {
+            start_time: cfg.start_time.clone().unwrap_or_else(|| "00:00".to_string()),
+            end_time: cfg.end_time.clone().unwrap_or_else(|| "02:00".to_string()),
+        };
+        cfg.schedule = Some(WeeklySchedule::uniform(window));
+        confy::store("git-touchfish-commit", None, cfg.clone())?;
+        println!("检测到旧版配置，已自动迁移为 Mon-Sun 统一的时间窗口安排。");
+    }
+
+    Ok(cfg)
+}
+
 fn set_time_range(start: &str, end: &str) -> Result<()> {
     let start_time = NaiveTime::parse_from_str(start, "%H:%M").map_err(|_| {
         format!(
@@ -79,34 +266,220 @@ fn set_time_range(start: &str, end: &str) -> Result<()> {
         return Err("开始时间必须早于结束时间".into());
     }
 
-    let cfg = AppConfig {
+    let mut cfg = load_config()?;
+    cfg.start_time = None;
+    cfg.end_time = None;
+    cfg.schedule = Some(WeeklySchedule::uniform(TimeWindow {
         start_time: start.to_string(),
         end_time: end.to_string(),
-    };
+    }));
 
     confy::store("git-touchfish-commit", None, cfg)?;
-    println!("时间区间已设置为: {} - {}", start, end);
+    println!("时间区间已设置为: {} - {}（应用到周一至周日全部七天，之前跳过的日期也会重新启用）", start, end);
+    println!("如需为某几天单独设置窗口或跳过周末，请直接编辑配置文件中的 `schedule`。");
     Ok(())
 }
 
 fn show_time_range() -> Result<()> {
-    let cfg: AppConfig = confy::load("git-touchfish-commit", None)?;
-    println!("当前时间区间: {} - {}", cfg.start_time, cfg.end_time);
+    let cfg = load_config()?;
+    let schedule = cfg
+        .schedule
+        .as_ref()
+        .expect("load_config 保证 schedule 一定存在");
+
+    println!("每周时间窗口安排:");
+    for (name, day) in schedule.days() {
+        if !day.enabled || day.windows.is_empty() {
+            println!("  {}: 跳过", name);
+            continue;
+        }
+        let windows: Vec<String> = day
+            .windows
+            .iter()
+            .map(|w| format!("{}-{}", w.start_time, w.end_time))
+            .collect();
+        println!("  {}: {}", name, windows.join(", "));
+    }
+    println!(
+        "当前时区: {}",
+        cfg.timezone.as_deref().unwrap_or("系统本地时区")
+    );
+    println!(
+        "显示格式: {} (示例: {})",
+        cfg.display_format,
+        Local::now().format(&cfg.display_format)
+    );
+    Ok(())
+}
+
+/// 校验一个字符串是否为合法的 strftime 格式，非法时返回带格式串的错误
+fn validate_strftime_format(format: &str) -> Result<()> {
+    if chrono::format::StrftimeItems::new(format)
+        .any(|item| matches!(item, chrono::format::Item::Error))
+    {
+        return Err(format!("无效的 strftime 格式: {}", format).into());
+    }
+    Ok(())
+}
+
+fn set_display_format(format: &str) -> Result<()> {
+    // 提前解析一遍格式串，避免保存一个非法的 strftime 格式
+    validate_strftime_format(format)?;
+
+    let mut cfg = load_config()?;
+    cfg.display_format = format.to_string();
+    confy::store("git-touchfish-commit", None, cfg)?;
+    println!(
+        "显示格式已设置为: {} (示例: {})",
+        format,
+        Local::now().format(format)
+    );
     Ok(())
 }
 
-fn run_commit(args: &[String]) -> Result<()> {
-    let random_datetime = generate_random_commit_time()?;
-    let formatted_time = random_datetime.to_rfc3339();
+/// 从命令行参数中提取 `--tz <IANA 时区>`，返回解析出的时区及剩余参数
+fn extract_tz_override(args: &[String]) -> Result<(Option<Tz>, Vec<String>)> {
+    let mut tz = None;
+    let mut rest = Vec::with_capacity(args.len());
 
-    println!("正在使用随机时间 {} 执行 git commit...", formatted_time);
+    let mut iter = args.iter().cloned();
+    while let Some(arg) = iter.next() {
+        if arg == "--tz" {
+            let value = iter
+                .next()
+                .ok_or("`--tz` 需要一个 IANA 时区参数，例如 `--tz Asia/Shanghai`")?;
+            tz = Some(
+                value
+                    .parse::<Tz>()
+                    .map_err(|_| format!("无效的 IANA 时区: {}", value))?,
+            );
+        } else {
+            rest.push(arg);
+        }
+    }
+
+    Ok((tz, rest))
+}
+
+/// 从命令行参数中提取 `--author-only` / `--committer-only` / `--both`
+fn extract_date_mode_override(args: &[String]) -> Result<(Option<DateMode>, Vec<String>)> {
+    let mut mode = None;
+    let mut rest = Vec::with_capacity(args.len());
+
+    for arg in args {
+        match arg.as_str() {
+            "--author-only" => mode = Some(DateMode::AuthorOnly),
+            "--committer-only" => mode = Some(DateMode::CommitterOnly),
+            "--both" => mode = Some(DateMode::Both),
+            _ => rest.push(arg.clone()),
+        }
+    }
+
+    Ok((mode, rest))
+}
+
+/// 从命令行参数中提取 `--spread <count>`
+fn extract_spread_override(args: &[String]) -> Result<(Option<usize>, Vec<String>)> {
+    let mut spread = None;
+    let mut rest = Vec::with_capacity(args.len());
+
+    let mut iter = args.iter().cloned();
+    while let Some(arg) = iter.next() {
+        if arg == "--spread" {
+            let value = iter
+                .next()
+                .ok_or("`--spread` 需要一个正整数参数，例如 `--spread 4`")?;
+            let count: usize = value
+                .parse()
+                .map_err(|_| format!("无效的 --spread 数量: {}", value))?;
+            if count == 0 {
+                return Err("`--spread` 的数量必须大于 0".into());
+            }
+            spread = Some(count);
+        } else {
+            rest.push(arg);
+        }
+    }
+
+    Ok((spread, rest))
+}
+
+/// 从命令行参数中提取 `--format <strftime>`
+fn extract_format_override(args: &[String]) -> Result<(Option<String>, Vec<String>)> {
+    let mut format = None;
+    let mut rest = Vec::with_capacity(args.len());
+
+    let mut iter = args.iter().cloned();
+    while let Some(arg) = iter.next() {
+        if arg == "--format" {
+            let value = iter.next().ok_or(
+                "`--format` 需要一个 strftime 格式参数，例如 `--format \"%Y-%m-%d %H:%M:%S %z\"`",
+            )?;
+            validate_strftime_format(&value)?;
+            format = Some(value);
+        } else {
+            rest.push(arg);
+        }
+    }
+
+    Ok((format, rest))
+}
+
+/// 根据 `date_mode` 计算最终要设置的作者时间 / 提交者时间（`None` 表示
+/// 该时间戳不随机化，交给 git 使用默认的当前时间）
+fn build_commit_dates(
+    tz_override: Option<Tz>,
+    mode_override: Option<DateMode>,
+    spread_count: Option<usize>,
+) -> Result<(Option<DateTime<FixedOffset>>, Option<DateTime<FixedOffset>>)> {
+    let cfg = load_config()?;
+    let mode = mode_override.unwrap_or(cfg.date_mode);
+
+    let random_time = match spread_count {
+        Some(count) => generate_spread_commit_time(tz_override, count)?,
+        None => generate_random_commit_time(tz_override)?,
+    };
+
+    let dates = match mode {
+        DateMode::Both => {
+            let committer_time = if cfg.stagger_committer {
+                let mut rng = rand::rng();
+                random_time + Duration::seconds(rng.random_range(1..=600))
+            } else {
+                random_time
+            };
+            (Some(random_time), Some(committer_time))
+        }
+        DateMode::AuthorOnly => (Some(random_time), None),
+        DateMode::CommitterOnly => (None, Some(random_time)),
+    };
+
+    Ok(dates)
+}
+
+fn run_commit(
+    args: &[String],
+    tz_override: Option<Tz>,
+    mode_override: Option<DateMode>,
+    spread_count: Option<usize>,
+    format_override: Option<String>,
+) -> Result<()> {
+    let (author_time, committer_time) = build_commit_dates(tz_override, mode_override, spread_count)?;
+    let format = format_override.unwrap_or(load_config()?.display_format);
+
+    println!(
+        "正在使用{}执行 git commit...",
+        describe_commit_dates(author_time, committer_time, &format)
+    );
 
     let mut commit_command = Command::new("git");
-    commit_command
-        .arg("commit")
-        .args(args)
-        .env("GIT_AUTHOR_DATE", &formatted_time)
-        .env("GIT_COMMITTER_DATE", &formatted_time);
+    commit_command.arg("commit").args(args);
+    if let Some(t) = author_time {
+        commit_command.env("GIT_AUTHOR_DATE", t.to_rfc3339());
+    }
+    if let Some(t) = committer_time {
+        commit_command.env("GIT_COMMITTER_DATE", t.to_rfc3339());
+    }
 
     let status = commit_command.status()?;
 
@@ -118,11 +491,19 @@ fn run_commit(args: &[String]) -> Result<()> {
     Ok(())
 }
 
-fn amend_commit_time(args: &[String]) -> Result<()> {
-    let random_datetime = generate_random_commit_time()?;
-    let formatted_time = random_datetime.to_rfc3339();
+fn amend_commit_time(
+    args: &[String],
+    tz_override: Option<Tz>,
+    mode_override: Option<DateMode>,
+    format_override: Option<String>,
+) -> Result<()> {
+    let (author_time, committer_time) = build_commit_dates(tz_override, mode_override, None)?;
+    let format = format_override.unwrap_or(load_config()?.display_format);
 
-    println!("正在使用随机时间 {} 修改最后一次 commit...", formatted_time);
+    println!(
+        "正在使用{}修改最后一次 commit...",
+        describe_commit_dates(author_time, committer_time, &format)
+    );
 
     let mut commit_command = Command::new("git");
     commit_command
@@ -130,9 +511,13 @@ fn amend_commit_time(args: &[String]) -> Result<()> {
         .arg("--amend")
         .arg("--no-edit")
         .arg("--reset-author")
-        .args(args)
-        .env("GIT_AUTHOR_DATE", &formatted_time)
-        .env("GIT_COMMITTER_DATE", &formatted_time);
+        .args(args);
+    if let Some(t) = author_time {
+        commit_command.env("GIT_AUTHOR_DATE", t.to_rfc3339());
+    }
+    if let Some(t) = committer_time {
+        commit_command.env("GIT_COMMITTER_DATE", t.to_rfc3339());
+    }
 
     let status = commit_command.status()?;
 
@@ -144,8 +529,57 @@ fn amend_commit_time(args: &[String]) -> Result<()> {
     Ok(())
 }
 
+/// 将一个时间戳渲染为 `<格式化时间> (<相对当前时间的人类可读描述>)`，
+/// 方便在应用前一眼确认生成的时间是否合理
+fn render_preview(dt: DateTime<FixedOffset>, format: &str) -> String {
+    let relative = HumanTime::from(dt - Local::now().fixed_offset());
+    format!("{} ({})", dt.format(format), relative)
+}
+
+/// 生成用于提示信息的“使用了哪些随机时间”描述
+fn describe_commit_dates(
+    author_time: Option<DateTime<FixedOffset>>,
+    committer_time: Option<DateTime<FixedOffset>>,
+    format: &str,
+) -> String {
+    match (author_time, committer_time) {
+        (Some(a), Some(c)) if a == c => format!("随机时间 {}", render_preview(a, format)),
+        (Some(a), Some(c)) => format!(
+            "随机作者时间 {} 和随机提交者时间 {}",
+            render_preview(a, format),
+            render_preview(c, format)
+        ),
+        (Some(a), None) => format!(
+            "随机作者时间 {}（提交者时间保持默认）",
+            render_preview(a, format)
+        ),
+        (None, Some(c)) => format!(
+            "随机提交者时间 {}（作者时间保持默认）",
+            render_preview(c, format)
+        ),
+        (None, None) => unreachable!("build_commit_dates 至少会随机化一个时间戳"),
+    }
+}
+
+/// 在给定时区（未指定则为系统本地时区）下，将一个 naive 时间解析为具体的
+/// `DateTime<FixedOffset>`。返回 `None` 表示该时间落在 DST 跳变的空洞中
+/// （既不属于前一个偏移也不属于后一个偏移），调用方应当顺延重试。
+fn resolve_in_timezone(tz: Option<Tz>, naive: NaiveDateTime) -> Option<DateTime<FixedOffset>> {
+    let result = match tz {
+        Some(tz) => tz.from_local_datetime(&naive).map(|dt| dt.fixed_offset()),
+        None => Local.from_local_datetime(&naive).map(|dt| dt.fixed_offset()),
+    };
+
+    match result {
+        LocalResult::Single(dt) => Some(dt),
+        // DST 回拨导致同一时刻对应两个偏移，两者都合法，取较早的一个
+        LocalResult::Ambiguous(dt, _) => Some(dt),
+        LocalResult::None => None,
+    }
+}
+
 /// 获取当前仓库最后一次 commit 的时间
-fn get_last_commit_time() -> Result<DateTime<Local>> {
+fn get_last_commit_time(tz: Option<Tz>) -> Result<DateTime<FixedOffset>> {
     // 使用 git log -1 --format=%ct 获取最后一次提交的 Unix 时间戳
     let output = Command::new("git")
         .args(["log", "-1", "--format=%ct"])
@@ -153,71 +587,472 @@ fn get_last_commit_time() -> Result<DateTime<Local>> {
 
     // 如果执行失败（例如不在 git 仓库中，或者没有 commit），默认返回一个很久以前的时间
     // 这样逻辑就会回退到使用 "今天"
+    let epoch = || -> DateTime<FixedOffset> {
+        match tz {
+            Some(tz) => tz.timestamp_opt(0, 0).unwrap().fixed_offset(),
+            None => Local.timestamp_opt(0, 0).unwrap().fixed_offset(),
+        }
+    };
+
     let output = match output {
         Ok(o) if o.status.success() => o,
-        _ => return Ok(Local.timestamp_opt(0, 0).unwrap()), // 1970-01-01
+        _ => return Ok(epoch()), // 1970-01-01
     };
 
     let timestamp_str = String::from_utf8(output.stdout)?.trim().to_string();
     if timestamp_str.is_empty() {
-        return Ok(Local.timestamp_opt(0, 0).unwrap());
+        return Ok(epoch());
     }
 
     let timestamp: i64 = timestamp_str.parse()?;
-    // 将时间戳转换为本地时间
-    Ok(Local.timestamp_opt(timestamp, 0).unwrap())
+    // 将时间戳转换为目标时区
+    let dt = match tz {
+        Some(tz) => tz.timestamp_opt(timestamp, 0).unwrap().fixed_offset(),
+        None => Local.timestamp_opt(timestamp, 0).unwrap().fixed_offset(),
+    };
+    Ok(dt)
 }
 
-/// 生成随机时间，保证晚于最后一次 commit
-fn generate_random_commit_time() -> Result<DateTime<Local>> {
-    let cfg: AppConfig = confy::load("git-touchfish-commit", None)?;
+/// 解析配置中生效的时区：命令行 `--tz` 优先，其次是配置文件中的 `timezone`
+fn effective_timezone(tz_override: Option<Tz>, cfg: &AppConfig) -> Result<Option<Tz>> {
+    match tz_override {
+        Some(tz) => Ok(Some(tz)),
+        None => cfg
+            .timezone
+            .as_deref()
+            .map(|name| {
+                name.parse::<Tz>()
+                    .map_err(|_| format!("配置文件中的时区无效: {}", name).into())
+            })
+            .transpose(),
+    }
+}
 
-    let start_time = NaiveTime::parse_from_str(&cfg.start_time, "%H:%M")?;
-    let end_time = NaiveTime::parse_from_str(&cfg.end_time, "%H:%M")?;
+/// 在一段任意的 `[start_dt, end_dt)` 范围内随机抽取一个时刻。如果抽到的
+/// naive 时间落在 DST 跳变的空洞里，不断向后微调（而不是直接 panic）
+/// 直到落在一个实际存在的时刻上
+fn draw_random_time_in_range(
+    tz: Option<Tz>,
+    start_dt: NaiveDateTime,
+    end_dt: NaiveDateTime,
+) -> Result<DateTime<FixedOffset>> {
+    let total_seconds = (end_dt - start_dt).num_seconds();
+    if total_seconds <= 0 {
+        return Err("时间区间无效，结束时间必须晚于开始时间".into());
+    }
 
-    // 1. 获取最后一次 commit 的时间
-    let last_commit_time = get_last_commit_time()?;
+    let mut rng = rand::rng();
+    let offset = rng.random_range(0..total_seconds);
+    let mut naive = start_dt + Duration::seconds(offset);
+
+    loop {
+        if let Some(dt) = resolve_in_timezone(tz, naive) {
+            return Ok(dt);
+        }
+        naive += Duration::minutes(1);
+    }
+}
 
-    // 2. 基础日期默认为“今天”
-    let now = Local::now();
+/// 基础日期默认为“今天”（在目标时区下）；如果最后一次提交的时间比今天
+/// 还晚（比如之前已经做过未来的提交），则从那一天开始，否则生成的
+/// “今天”肯定会早于“最后提交”
+fn resolve_base_date(tz: Option<Tz>, last_commit_time: DateTime<FixedOffset>) -> NaiveDate {
+    let now = match tz {
+        Some(tz) => tz
+            .from_utc_datetime(&chrono::Utc::now().naive_utc())
+            .fixed_offset(),
+        None => Local::now().fixed_offset(),
+    };
     let mut base_date = now.date_naive();
 
-    // 如果最后一次提交的时间比今天还晚（比如之前已经做过未来的提交），
-    // 那么基础日期至少要从那一天开始，否则生成的“今天”肯定会早于“最后提交”
     if last_commit_time.date_naive() > base_date {
         base_date = last_commit_time.date_naive();
     }
 
-    // 3. 在基础日期上构建随机时间
+    base_date
+}
+
+/// 从 `date` 开始（含 `date` 本身）向后查找第一个启用且配置了时间窗口
+/// 的日期，用于跳过周末等被禁用的天
+fn first_enabled_day_on_or_after(schedule: &WeeklySchedule, mut date: NaiveDate) -> NaiveDate {
+    for _ in 0..8 {
+        let day = schedule.day(date.weekday());
+        if day.enabled && !day.windows.is_empty() {
+            return date;
+        }
+        date += Duration::days(1);
+    }
+    // 一周七天都被禁用，理论上不应发生；原样返回交由调用方报错
+    date
+}
+
+/// 在 `date` 当天的安排里，按各时间窗口的时长加权随机选择一个窗口，再在
+/// 窗口内抽取一个具体时刻
+fn draw_random_time_in_schedule(
+    tz: Option<Tz>,
+    schedule: &WeeklySchedule,
+    date: NaiveDate,
+) -> Result<DateTime<FixedOffset>> {
+    let day = schedule.day(date.weekday());
+    if !day.enabled || day.windows.is_empty() {
+        return Err(format!("{} 当天未启用任何时间窗口", date).into());
+    }
+
+    let mut windows = Vec::with_capacity(day.windows.len());
+    for w in &day.windows {
+        let start = NaiveTime::parse_from_str(&w.start_time, "%H:%M")?;
+        let end = NaiveTime::parse_from_str(&w.end_time, "%H:%M")?;
+        let seconds = (end - start).num_seconds();
+        if seconds <= 0 {
+            return Err(format!(
+                "时间窗口无效: {}-{}，结束时间必须晚于开始时间",
+                w.start_time, w.end_time
+            )
+            .into());
+        }
+        windows.push((start, end, seconds));
+    }
+
+    let total_seconds: i64 = windows.iter().map(|(_, _, s)| s).sum();
+    let mut pick = rand::rng().random_range(0..total_seconds);
+    let (start, end, _) = windows
+        .into_iter()
+        .find(|(_, _, seconds)| {
+            if pick < *seconds {
+                true
+            } else {
+                pick -= seconds;
+                false
+            }
+        })
+        .expect("total_seconds 等于各窗口时长之和，必然能选中一个窗口");
+
+    draw_random_time_in_range(tz, date.and_time(start), date.and_time(end))
+}
+
+/// 生成随机时间，保证晚于最后一次 commit
+fn generate_random_commit_time(tz_override: Option<Tz>) -> Result<DateTime<FixedOffset>> {
+    let cfg = load_config()?;
+    let tz = effective_timezone(tz_override, &cfg)?;
+    let schedule = cfg
+        .schedule
+        .as_ref()
+        .expect("load_config 保证 schedule 一定存在");
+
+    let last_commit_time = get_last_commit_time(tz)?;
+    let mut base_date =
+        first_enabled_day_on_or_after(schedule, resolve_base_date(tz, last_commit_time));
+
+    let mut final_datetime = draw_random_time_in_schedule(tz, schedule, base_date)?;
+
+    // 核心逻辑：如果生成的随机时间 <= 最后一次提交时间，则顺延到下一个
+    // 启用的日期重新抽取。这种情况通常发生在：
+    // a. 今天已经提交过了，且最后一次提交时间晚于刚才随机出的时间。
+    // b. 设定的时间窗口整体早于最后一次提交时间。
+    while final_datetime <= last_commit_time {
+        println!(
+            "生成的随机时间 ({}) 早于最后一次提交 ({})，自动顺延到下一个启用日期...",
+            final_datetime.format("%Y-%m-%d %H:%M:%S"),
+            last_commit_time.format("%Y-%m-%d %H:%M:%S")
+        );
+
+        base_date = first_enabled_day_on_or_after(schedule, base_date + Duration::days(1));
+        final_datetime = draw_random_time_in_schedule(tz, schedule, base_date)?;
+    }
+
+    Ok(final_datetime)
+}
+
+/// 从持久化的 `SpreadSession` 游标中取出下一个时间槽，并在槽内抽取一个
+/// 抖动后的随机时刻。`count` 或日期变化、或游标已经用满一轮时，会开启
+/// 新一轮分摊
+fn next_spread_slot(
+    tz: Option<Tz>,
+    base_date: NaiveDate,
+    start_time: NaiveTime,
+    end_time: NaiveTime,
+    count: usize,
+) -> Result<DateTime<FixedOffset>> {
+    let mut session: SpreadSession = confy::load("git-touchfish-commit", Some("spread_session"))?;
+
+    let date_str = base_date.format("%Y-%m-%d").to_string();
+    if session.date.as_deref() != Some(date_str.as_str())
+        || session.count != count
+        || session.cursor >= count
+    {
+        session = SpreadSession {
+            date: Some(date_str),
+            count,
+            cursor: 0,
+        };
+    }
+
     let start_datetime = base_date.and_time(start_time);
     let end_datetime = base_date.and_time(end_time);
-
     let total_seconds = (end_datetime - start_datetime).num_seconds();
     if total_seconds <= 0 {
         return Err("时间范围无效，结束时间必须晚于开始时间".into());
     }
+    if count as i64 > total_seconds {
+        return Err(format!(
+            "--spread 的数量 {} 超过了当天窗口可分摊的秒数（最多 {} 个），请减少数量或扩大时间窗口",
+            count, total_seconds
+        )
+        .into());
+    }
 
-    let mut rng = rand::rng();
-    let random_offset_seconds = rng.random_range(0..=total_seconds);
+    let slot_seconds = total_seconds / count as i64;
+    let slot_start = start_datetime + Duration::seconds(slot_seconds * session.cursor as i64);
+    let slot_end = if session.cursor + 1 == count {
+        end_datetime
+    } else {
+        slot_start + Duration::seconds(slot_seconds)
+    };
 
-    // 初始生成的随机时间
-    let mut random_datetime_naive = start_datetime + Duration::seconds(random_offset_seconds);
-    let mut final_datetime = Local.from_local_datetime(&random_datetime_naive).unwrap();
+    let final_datetime = draw_random_time_in_range(tz, slot_start, slot_end)?;
 
-    // 4. 核心逻辑：如果生成的随机时间 <= 最后一次提交时间，则顺延一天
-    // 这种情况通常发生在：
-    // a. 今天已经提交过了，且最后一次提交时间晚于刚才随机出的时间。
-    // b. 设定的时间区间（如 09:00-10:00）整体早于最后一次提交时间（如 11:00）。
-    if final_datetime <= last_commit_time {
+    session.cursor += 1;
+    confy::store("git-touchfish-commit", Some("spread_session"), session)?;
+
+    Ok(final_datetime)
+}
+
+/// `--spread` 按一个单一区间切分时间槽，多窗口的日子取当天第一个启用
+/// 的窗口作为分摊区间（更复杂的多窗口分摊请直接使用不带 `--spread` 的
+/// 随机模式，它会按时长加权挑选窗口）
+fn spread_window_for_day(schedule: &WeeklySchedule, date: NaiveDate) -> Result<(NaiveTime, NaiveTime)> {
+    let day = schedule.day(date.weekday());
+    let window = day
+        .windows
+        .first()
+        .ok_or_else(|| format!("{} 当天未启用任何时间窗口，无法使用 --spread", date))?;
+    Ok((
+        NaiveTime::parse_from_str(&window.start_time, "%H:%M")?,
+        NaiveTime::parse_from_str(&window.end_time, "%H:%M")?,
+    ))
+}
+
+/// 生成分摊模式下的随机时间：把当天的时间窗口切成 `count` 个时间槽，
+/// 连续多次调用依次落在下一个槽内，从而让一批 commit 看起来分散在整个
+/// 摸鱼窗口中，而不是互相独立随机、容易扎堆
+fn generate_spread_commit_time(tz_override: Option<Tz>, count: usize) -> Result<DateTime<FixedOffset>> {
+    let cfg = load_config()?;
+    let tz = effective_timezone(tz_override, &cfg)?;
+    let schedule = cfg
+        .schedule
+        .as_ref()
+        .expect("load_config 保证 schedule 一定存在");
+
+    let last_commit_time = get_last_commit_time(tz)?;
+    let mut base_date =
+        first_enabled_day_on_or_after(schedule, resolve_base_date(tz, last_commit_time));
+
+    let (start_time, end_time) = spread_window_for_day(schedule, base_date)?;
+    let mut final_datetime = next_spread_slot(tz, base_date, start_time, end_time, count)?;
+
+    while final_datetime <= last_commit_time {
         println!(
-            "生成的随机时间 ({}) 早于最后一次提交 ({})，自动顺延一天...",
+            "生成的随机时间 ({}) 早于最后一次提交 ({})，自动顺延到下一个启用日期并开启新一轮分摊...",
             final_datetime.format("%Y-%m-%d %H:%M:%S"),
             last_commit_time.format("%Y-%m-%d %H:%M:%S")
         );
 
-        random_datetime_naive += Duration::days(1);
-        final_datetime = Local.from_local_datetime(&random_datetime_naive).unwrap();
+        base_date = first_enabled_day_on_or_after(schedule, base_date + Duration::days(1));
+        let (start_time, end_time) = spread_window_for_day(schedule, base_date)?;
+        final_datetime = next_spread_slot(tz, base_date, start_time, end_time, count)?;
     }
 
     Ok(final_datetime)
 }
+
+/// 将仓库中一段历史的提交时间重新改写为落在 touch-fish 窗口内、严格递增的随机时间
+fn reschedule_history(args: &[String]) -> Result<()> {
+    let (tz_override, rest) = extract_tz_override(args)?;
+
+    let mut from_rev: Option<String> = None;
+    let mut apply = false;
+    let mut iter = rest.iter().cloned();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--from" => {
+                from_rev = Some(
+                    iter.next()
+                        .ok_or("`--from` 需要一个 commit/引用参数，例如 `--from HEAD~3`")?,
+                );
+            }
+            "--apply" => apply = true,
+            other => return Err(format!("未知参数: {}", other).into()),
+        }
+    }
+
+    let cfg = load_config()?;
+    let tz = effective_timezone(tz_override, &cfg)?;
+    let schedule = cfg
+        .schedule
+        .as_ref()
+        .expect("load_config 保证 schedule 一定存在");
+
+    let repo = Repository::open(".")?;
+
+    let from_oid = from_rev
+        .as_deref()
+        .map(|rev| repo.revparse_single(rev).map(|obj| obj.id()))
+        .transpose()?;
+
+    let mut revwalk = repo.revwalk()?;
+    revwalk.push_head()?;
+    revwalk.set_sorting(Sort::TOPOLOGICAL | Sort::TIME | Sort::REVERSE)?;
+
+    // 只保留 --from 指定 commit 之后的提交；未指定则从根提交开始全部改写
+    let mut commits = Vec::new();
+    let mut past_from = from_oid.is_none();
+    for oid in revwalk {
+        let oid = oid?;
+        if !past_from {
+            if Some(oid) == from_oid {
+                past_from = true;
+            }
+            continue;
+        }
+        commits.push(oid);
+    }
+
+    if commits.is_empty() {
+        println!("没有需要改期的 commit。");
+        return Ok(());
+    }
+
+    // reschedule 会把每个 commit 依次重建为单一父提交的线性历史，
+    // 一旦范围内出现合并提交，第二个父提交会被静默丢弃，等于破坏 DAG，
+    // 因此直接拒绝，而不是悄悄改写拓扑结构
+    for oid in &commits {
+        let commit = repo.find_commit(*oid)?;
+        if commit.parent_count() > 1 {
+            return Err(format!(
+                "commit {} 是合并提交（{} 个父提交），reschedule 无法在不破坏历史拓扑的情况下改写它，请缩小 --from 范围避开合并提交",
+                &oid.to_string()[..7],
+                commit.parent_count()
+            )
+            .into());
+        }
+    }
+
+    struct Plan {
+        oid: git2::Oid,
+        old: DateTime<FixedOffset>,
+        new: DateTime<FixedOffset>,
+    }
+
+    // 起点：若指定了 --from，则新时间必须严格晚于该 commit 的原始时间
+    let mut last_assigned = match from_oid {
+        Some(oid) => Some(commit_time(&repo.find_commit(oid)?)),
+        None => None,
+    };
+
+    // 未指定 --from 时，以第一个待改期 commit 自身的原始日期为起点，
+    // 而不是今天——否则会把整段历史都改到今天及之后，对“改期”来说
+    // 是反直觉的
+    let mut base_date = first_enabled_day_on_or_after(
+        schedule,
+        match last_assigned {
+            Some(dt) => dt.date_naive(),
+            None => commit_time(&repo.find_commit(commits[0])?).date_naive(),
+        },
+    );
+
+    let mut plans = Vec::with_capacity(commits.len());
+    for oid in &commits {
+        let commit = repo.find_commit(*oid)?;
+        let old = commit_time(&commit);
+
+        let new = loop {
+            let candidate = draw_random_time_in_schedule(tz, schedule, base_date)?;
+            if let Some(last) = last_assigned {
+                if candidate <= last {
+                    // 复用与 generate_random_commit_time 相同的顺延逻辑
+                    base_date = first_enabled_day_on_or_after(schedule, base_date + Duration::days(1));
+                    continue;
+                }
+            }
+            break candidate;
+        };
+
+        last_assigned = Some(new);
+        plans.push(Plan { oid: *oid, old, new });
+    }
+
+    if from_oid.is_none() {
+        println!("（未指定 --from，新时间将以第一个 commit 的原始日期为起点顺延）");
+    }
+    println!("{:<9} {:<25} {:<25}", "commit", "旧时间", "新时间");
+    for plan in &plans {
+        println!(
+            "{:<9} {:<25} {:<25}",
+            &plan.oid.to_string()[..7],
+            plan.old.format("%Y-%m-%d %H:%M:%S %z"),
+            plan.new.format("%Y-%m-%d %H:%M:%S %z")
+        );
+    }
+
+    if !apply {
+        println!("\n以上为预览（dry-run），加上 --apply 以实际改写历史。");
+        return Ok(());
+    }
+
+    // 从 from_oid（若无则为根提交）开始按顺序重建 commit，树和提交信息保持
+    // 不变，只替换作者/提交者时间；后续 commit 依次以新 commit 为父提交
+    let mut parent_oid = from_oid;
+    for plan in &plans {
+        let commit = repo.find_commit(plan.oid)?;
+        let tree = commit.tree()?;
+
+        let parents: Vec<git2::Commit> = match parent_oid {
+            Some(oid) => vec![repo.find_commit(oid)?],
+            None => commit.parents().collect(),
+        };
+        let parent_refs: Vec<&git2::Commit> = parents.iter().collect();
+
+        let new_time = git2::Time::new(plan.new.timestamp(), plan.new.offset().local_minus_utc() / 60);
+        let author = git2::Signature::new(
+            commit.author().name().unwrap_or_default(),
+            commit.author().email().unwrap_or_default(),
+            &new_time,
+        )?;
+        let committer = git2::Signature::new(
+            commit.committer().name().unwrap_or_default(),
+            commit.committer().email().unwrap_or_default(),
+            &new_time,
+        )?;
+
+        let new_oid = repo.commit(
+            None,
+            &author,
+            &committer,
+            commit.message().unwrap_or_default(),
+            &tree,
+            &parent_refs,
+        )?;
+
+        parent_oid = Some(new_oid);
+    }
+
+    if let Some(new_head) = parent_oid {
+        let head_ref_name = repo.head()?.name().ok_or("无法获取当前分支引用名")?.to_string();
+        repo.reference(
+            &head_ref_name,
+            new_head,
+            true,
+            "git-tc reschedule: 重写提交时间",
+        )?;
+    }
+
+    println!("改期完成，已更新 {} 个 commit。", plans.len());
+    Ok(())
+}
+
+/// 将 git2 的 `Time`（UTC 秒数 + 分钟偏移）转换为 `DateTime<FixedOffset>`
+fn commit_time(commit: &git2::Commit) -> DateTime<FixedOffset> {
+    let time = commit.time();
+    let offset = FixedOffset::east_opt(time.offset_minutes() * 60).unwrap();
+    offset.timestamp_opt(time.seconds(), 0).unwrap()
+}